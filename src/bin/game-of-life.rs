@@ -15,10 +15,30 @@ use winit::{
 async fn run() {
     println!("hello world");
 
+    #[cfg(not(target_arch = "wasm32"))]
     pretty_env_logger::init();
+    #[cfg(target_arch = "wasm32")]
+    {
+        std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+        console_log::init_with_level(log::Level::Debug).expect("Failed to initialize logger");
+    }
     info!("Starting");
 
     let (mut state, event_loop) = State::new().await;
+    state.randomize();
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::WindowExtWebSys;
+        web_sys::window()
+            .and_then(|win| win.document())
+            .and_then(|doc| doc.body())
+            .and_then(|body| {
+                body.append_child(&web_sys::Element::from(state.window.canvas()))
+                    .ok()
+            })
+            .expect("Couldn't append canvas to document body");
+    }
 
     event_loop.run(move |event, _, control_flow| match event {
         winit::event::Event::MainEventsCleared => {
@@ -29,7 +49,13 @@ async fn run() {
         }
         WindowEvent { window_id, event } if window_id == state.window.id() => match event {
             winit::event::WindowEvent::KeyboardInput { input, .. } => {
-                handle_keyboard_input(input, control_flow);
+                handle_keyboard_input(input, control_flow, &mut state);
+            }
+            winit::event::WindowEvent::Resized(new_size) => {
+                state.resize(new_size);
+            }
+            winit::event::WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                state.resize(*new_inner_size);
             }
             _ => {}
         },
@@ -37,11 +63,18 @@ async fn run() {
     });
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
     pollster::block_on(run());
 }
 
-pub fn handle_keyboard_input(input: KeyboardInput, control_flow: &mut ControlFlow) {
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(start)]
+pub fn main_wasm() {
+    wasm_bindgen_futures::spawn_local(run());
+}
+
+pub fn handle_keyboard_input(input: KeyboardInput, control_flow: &mut ControlFlow, state: &mut State) {
     if input.virtual_keycode.is_none() {
         return;
     }
@@ -50,6 +83,8 @@ pub fn handle_keyboard_input(input: KeyboardInput, control_flow: &mut ControlFlo
         VirtualKeyCode::Escape if input.state == ElementState::Pressed => {
             *control_flow = ControlFlow::Exit;
         }
-        _ => {}
+        _ => {
+            state.process_keyboard_input(input);
+        }
     }
 }
@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use cgmath::{InnerSpace, Matrix4, Point3, Rad, SquareMatrix, Vector3};
+use wgpu::util::DeviceExt;
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode};
+
+// wgpu's clip space is [0, 1] in z rather than OpenGL's [-1, 1], and cgmath's
+// perspective matrix assumes the latter, so the projection needs correcting.
+#[rustfmt::skip]
+const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+pub struct Camera {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    pub up: Vector3<f32>,
+    pub aspect: f32,
+    pub fovy: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
+
+impl Camera {
+    pub fn new(aspect: f32) -> Self {
+        Self {
+            eye: Point3::new(0.0, 0.0, 2.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            up: Vector3::unit_y(),
+            aspect,
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+        }
+    }
+
+    pub fn build_view_projection_matrix(&self) -> Matrix4<f32> {
+        let view = Matrix4::look_at_rh(self.eye, self.target, self.up);
+        let proj = cgmath::perspective(Rad::from(cgmath::Deg(self.fovy)), self.aspect, self.znear, self.zfar);
+        OPENGL_TO_WGPU_MATRIX * proj * view
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    view_proj: [[f32; 4]; 4],
+}
+
+impl CameraUniform {
+    pub fn new() -> Self {
+        Self {
+            view_proj: Matrix4::identity().into(),
+        }
+    }
+
+    pub fn update(&mut self, camera: &Camera) {
+        self.view_proj = camera.build_view_projection_matrix().into();
+    }
+}
+
+/// Uploads a `Camera`'s view-projection matrix and exposes the bind group
+/// the shader reads it from.
+pub struct CameraResource {
+    pub camera: Camera,
+    uniform: CameraUniform,
+    buffer: wgpu::Buffer,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl CameraResource {
+    pub fn new(device: &wgpu::Device, aspect: f32) -> Self {
+        let camera = Camera::new(aspect);
+        let mut uniform = CameraUniform::new();
+        uniform.update(&camera);
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera uniform buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Camera bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Camera bind group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            camera,
+            uniform,
+            buffer,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+
+    pub fn resize(&mut self, aspect: f32) {
+        self.camera.aspect = aspect;
+    }
+
+    /// Recompute the uniform from the current camera state and upload it.
+    pub fn sync(&mut self, queue: &wgpu::Queue) {
+        self.uniform.update(&self.camera);
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+    }
+}
+
+/// Pans the camera with WASD/arrow keys and zooms in/out with +/-.
+pub struct CameraController {
+    speed: f32,
+    forward_pressed: bool,
+    backward_pressed: bool,
+    left_pressed: bool,
+    right_pressed: bool,
+    zoom_in_pressed: bool,
+    zoom_out_pressed: bool,
+}
+
+impl CameraController {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            forward_pressed: false,
+            backward_pressed: false,
+            left_pressed: false,
+            right_pressed: false,
+            zoom_in_pressed: false,
+            zoom_out_pressed: false,
+        }
+    }
+
+    /// Returns `true` if `input` was one of the keys this controller handles.
+    pub fn process_keyboard_input(&mut self, input: KeyboardInput) -> bool {
+        let Some(key) = input.virtual_keycode else {
+            return false;
+        };
+        let pressed = input.state == ElementState::Pressed;
+        match key {
+            VirtualKeyCode::W | VirtualKeyCode::Up => {
+                self.forward_pressed = pressed;
+                true
+            }
+            VirtualKeyCode::S | VirtualKeyCode::Down => {
+                self.backward_pressed = pressed;
+                true
+            }
+            VirtualKeyCode::A | VirtualKeyCode::Left => {
+                self.left_pressed = pressed;
+                true
+            }
+            VirtualKeyCode::D | VirtualKeyCode::Right => {
+                self.right_pressed = pressed;
+                true
+            }
+            VirtualKeyCode::Equals | VirtualKeyCode::NumpadAdd => {
+                self.zoom_in_pressed = pressed;
+                true
+            }
+            VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => {
+                self.zoom_out_pressed = pressed;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Advance the camera by `dt` worth of movement. `speed` is in world
+    /// units per second, so panning/zooming stays consistent regardless of
+    /// the render frame rate.
+    pub fn update_camera(&self, camera: &mut Camera, dt: Duration) {
+        let step = self.speed * dt.as_secs_f32();
+
+        let forward = camera.target - camera.eye;
+        let forward_norm = forward.normalize();
+        let forward_mag = forward.magnitude();
+        let right = forward_norm.cross(camera.up);
+
+        // Zoom by moving the eye along the forward axis, never crossing the target.
+        if self.zoom_in_pressed && forward_mag > step {
+            camera.eye += forward_norm * step;
+        }
+        if self.zoom_out_pressed {
+            camera.eye -= forward_norm * step;
+        }
+
+        // Pan both the eye and the target together so "forward" doesn't drift.
+        if self.forward_pressed {
+            camera.eye += camera.up * step;
+            camera.target += camera.up * step;
+        }
+        if self.backward_pressed {
+            camera.eye -= camera.up * step;
+            camera.target -= camera.up * step;
+        }
+        if self.right_pressed {
+            camera.eye += right * step;
+            camera.target += right * step;
+        }
+        if self.left_pressed {
+            camera.eye -= right * step;
+            camera.target -= right * step;
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use rand::Rng;
+use wgpu::util::DeviceExt;
+
+/// Grid dimensions shared between the compute and render bind groups.
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct GridUniform {
+    width: u32,
+    height: u32,
+}
+
+/// A Game of Life simulation backed by two ping-ponged storage buffers.
+///
+/// `step` dispatches the compute shader that reads the current buffer and
+/// writes the next generation into the other one, then swaps which buffer
+/// is considered "current". The render path only ever reads the current
+/// buffer, so rendering and simulation never race.
+pub struct GolGrid {
+    pub width: u32,
+    pub height: u32,
+    buffers: [wgpu::Buffer; 2],
+    read_index: usize,
+    compute_pipeline: wgpu::ComputePipeline,
+    compute_bind_groups: [wgpu::BindGroup; 2],
+    pub render_bind_group_layout: wgpu::BindGroupLayout,
+    render_bind_groups: [wgpu::BindGroup; 2],
+}
+
+impl GolGrid {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let cell_count = (width * height) as usize;
+        let cells = vec![0u32; cell_count];
+
+        let make_buffer = |label: &str| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(&cells),
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+            })
+        };
+        let buffers = [make_buffer("GoL cell buffer A"), make_buffer("GoL cell buffer B")];
+
+        let grid_uniform = GridUniform { width, height };
+        let grid_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("GoL grid uniform buffer"),
+            contents: bytemuck::bytes_of(&grid_uniform),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("GoL compute bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        // One compute bind group per "which buffer is currently the read side".
+        let compute_bind_groups = [
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("GoL compute bind group (read A, write B)"),
+                layout: &compute_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffers[0].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: buffers[1].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: grid_uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            }),
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("GoL compute bind group (read B, write A)"),
+                layout: &compute_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffers[1].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: buffers[0].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: grid_uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            }),
+        ];
+
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("GoL compute pipeline layout"),
+                bind_group_layouts: &[&compute_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let compute_shader_module =
+            device.create_shader_module(wgpu::include_wgsl!("../shaders/gol.wgsl"));
+
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("GoL compute pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &compute_shader_module,
+            entry_point: "cs_main",
+        });
+
+        let render_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("GoL render bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let render_bind_groups = [
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("GoL render bind group (A current)"),
+                layout: &render_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffers[0].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: grid_uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            }),
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("GoL render bind group (B current)"),
+                layout: &render_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffers[1].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: grid_uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            }),
+        ];
+
+        Self {
+            width,
+            height,
+            buffers,
+            read_index: 0,
+            compute_pipeline,
+            compute_bind_groups,
+            render_bind_group_layout,
+            render_bind_groups,
+        }
+    }
+
+    pub fn cell_count(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// The bind group that should be bound to group 1 in `vs_main`; always
+    /// points at the buffer holding the latest settled generation.
+    pub fn current_render_bind_group(&self) -> &wgpu::BindGroup {
+        &self.render_bind_groups[self.read_index]
+    }
+
+    /// Dispatch one generation of the simulation and swap the read/write buffers.
+    pub fn step(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("GoL compute command encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("GoL compute pass"),
+            });
+            compute_pass.set_pipeline(&self.compute_pipeline);
+            compute_pass.set_bind_group(0, &self.compute_bind_groups[self.read_index], &[]);
+            compute_pass.dispatch_workgroups(
+                (self.width + 7) / 8,
+                (self.height + 7) / 8,
+                1,
+            );
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        self.read_index = 1 - self.read_index;
+    }
+
+    /// Replace the current generation with uniformly random live/dead cells.
+    pub fn randomize(&mut self, queue: &wgpu::Queue) {
+        let mut rng = rand::thread_rng();
+        let cells: Vec<u32> = (0..self.cell_count())
+            .map(|_| rng.gen_bool(0.5) as u32)
+            .collect();
+        queue.write_buffer(
+            &self.buffers[self.read_index],
+            0,
+            bytemuck::cast_slice(&cells),
+        );
+    }
+
+    /// Set a single cell's alive state in the current generation.
+    pub fn set_cell(&mut self, queue: &wgpu::Queue, x: u32, y: u32, alive: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let index = (y * self.width + x) as wgpu::BufferAddress;
+        let value: u32 = alive as u32;
+        queue.write_buffer(
+            &self.buffers[self.read_index],
+            index * std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            bytemuck::bytes_of(&value),
+        );
+    }
+}
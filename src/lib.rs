@@ -1,21 +1,48 @@
 #![allow(clippy::collapsible_match)]
 #![allow(clippy::single_match)]
 
-use std::time::Instant;
+mod camera;
+mod gol;
+mod mesh;
+mod post_process;
+mod texture;
 
-use cgmath::{SquareMatrix, Vector2, Vector3};
+pub use camera::{Camera, CameraController, CameraResource};
+pub use gol::GolGrid;
+pub use mesh::Mesh;
+pub use post_process::PostProcessChain;
+pub use texture::Texture;
+
+use std::time::{Duration, Instant};
+
+use cgmath::SquareMatrix;
 use log::{debug, warn};
 use wgpu::{
     include_wgsl, util::DeviceExt, Adapter, ColorTargetState, Device, PipelineLayout,
     PrimitiveState, Queue, RenderPipeline, RequestAdapterOptions, ShaderModule, Surface,
     TextureViewDimension, VertexBufferLayout,
 };
-use winit::{dpi::LogicalSize, event_loop::EventLoop, window::Window};
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    event::KeyboardInput,
+    event_loop::EventLoop,
+    window::Window,
+};
+
+/// Simulation steps happen at this rate regardless of how fast we're rendering.
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+/// Default dimensions of the Game of Life grid.
+const GRID_WIDTH: u32 = 64;
+const GRID_HEIGHT: u32 = 64;
 
 pub struct State {
     pub window: Window,
     counter: Instant,
+    last_tick: Instant,
+    tick_rate: Duration,
     surface: Surface,
+    config: wgpu::SurfaceConfiguration,
     _adapter: Adapter,
     device: Device,
     queue: Queue,
@@ -23,86 +50,142 @@ pub struct State {
     _pipeline_layout: PipelineLayout,
     render_pipeline: RenderPipeline,
     vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
     model_bind_group: wgpu::BindGroup,
-    instance_buffer: wgpu::Buffer,
+    gol: GolGrid,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    texture: Texture,
+    camera: CameraResource,
+    camera_controller: CameraController,
+    post_process: PostProcessChain,
 }
 
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Vertex {
     pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
 }
 
 pub const TRIANGLE_VERTICES: [Vertex; 3] = [
     Vertex {
         position: [-0.5, -0.5, 0.0],
+        tex_coords: [0.0, 1.0],
     },
     Vertex {
         position: [0.0, 0.5, 0.0],
+        tex_coords: [0.5, 0.0],
     },
     Vertex {
         position: [0.5, -0.5, 0.0],
+        tex_coords: [1.0, 1.0],
     },
 ];
 
 pub const SQUARE_VERTICES: [Vertex; 4] = [
     Vertex {
         position: [-0.5, -0.5, 0.0],
+        tex_coords: [0.0, 1.0],
     },
     Vertex {
         position: [-0.5, 0.5, 0.0],
+        tex_coords: [0.0, 0.0],
     },
     Vertex {
         position: [0.5, -0.5, 0.0],
+        tex_coords: [1.0, 1.0],
     },
     Vertex {
         position: [0.5, 0.5, 0.0],
+        tex_coords: [1.0, 0.0],
     },
 ];
 
-#[repr(C)]
-#[derive(Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
-struct InstanceRaw {
-    position: [[f32; 4]; 4],
-}
+impl State {
+    /// Advance the simulation by one generation if `tick_rate` has elapsed
+    /// since the last step. Decoupled from `draw` so simulation speed
+    /// doesn't depend on render FPS.
+    pub fn step(&mut self) {
+        if self.last_tick.elapsed() >= self.tick_rate {
+            self.gol.step(&self.device, &self.queue);
+            self.last_tick = Instant::now();
+        }
+    }
 
-struct Instance {
-    position: Vector2<f32>,
-}
+    pub fn randomize(&mut self) {
+        self.gol.randomize(&self.queue);
+    }
 
-impl From<Instance> for InstanceRaw {
-    fn from(instance: Instance) -> Self {
-        Self {
-            position: cgmath::Matrix4::from_translation(Vector3::new(
-                instance.position.x,
-                instance.position.y,
-                0.0,
-            ))
-            .into(),
-        }
+    pub fn set_cell(&mut self, x: u32, y: u32, alive: bool) {
+        self.gol.set_cell(&self.queue, x, y, alive);
+    }
+
+    /// Forward a keyboard event to the camera controller. Returns `true` if
+    /// the controller recognized the key (so callers can skip other handling).
+    pub fn process_keyboard_input(&mut self, input: KeyboardInput) -> bool {
+        self.camera_controller.process_keyboard_input(input)
     }
-}
 
-// const INSTANCE_DATA: [Instance; 2] = [
-//     Instance {
-//         position: Vector2::new(-0.5, -0.5),
-//     },
-//     Instance {
-//         position: Vector2::new(0.5, 0.6),
-//     },
-// ];
+    /// Append a new full-screen post-processing stage. `fragment_wgsl` need
+    /// only define `fs_main`; it can read `source_texture`/`source_sampler`
+    /// (group 0) and the `post` resolution/time uniform (group 1).
+    pub fn add_post_pass(&mut self, fragment_wgsl: &str) {
+        self.post_process.add_pass(&self.device, fragment_wgsl);
+    }
 
-const INSTANCE_DATA: [Instance; 1] = [Instance {
-    position: Vector2::new(0.0, 0.0),
-}];
+    /// Replace the geometry drawn every frame with `mesh`.
+    pub fn upload_mesh(&mut self, mesh: &Mesh) {
+        self.vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex buffer"),
+            contents: bytemuck::cast_slice(&mesh.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.index_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index buffer"),
+            contents: bytemuck::cast_slice(&mesh.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        self.num_indices = mesh.indices.len() as u32;
+    }
+
+    /// Decode `bytes` as an image and use it as the texture sampled in `fs_main`.
+    pub fn load_texture(&mut self, bytes: &[u8]) {
+        self.texture = Texture::from_bytes(
+            &self.device,
+            &self.queue,
+            bytes,
+            &self.texture_bind_group_layout,
+            "Loaded texture",
+        );
+    }
+
+    /// Reconfigure the surface for a new window size. Called on
+    /// `WindowEvent::Resized`/`ScaleFactorChanged`, and internally to
+    /// recover from `SurfaceError::Lost`/`Outdated`.
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+        self.surface.configure(&self.device, &self.config);
+        self.camera
+            .resize(self.config.width as f32 / self.config.height as f32);
+        self.post_process
+            .resize(&self.device, self.config.width, self.config.height);
+    }
 
-impl State {
     pub fn draw(&mut self) {
         let dt = self.counter.elapsed();
         self.counter = Instant::now();
         let fps = 1.0 / dt.as_secs_f32();
         debug!("{fps:02} fps");
 
+        self.step();
+        self.camera_controller.update_camera(&mut self.camera.camera, dt);
+        self.camera.sync(&self.queue);
+
         let surface_texture = match self.surface.get_current_texture() {
             Ok(surface_texture) => surface_texture,
             Err(wgpu::SurfaceError::Timeout) => {
@@ -111,7 +194,11 @@ impl State {
             Err(wgpu::SurfaceError::OutOfMemory) => {
                 panic!("Out of memory!")
             }
-            // Err(_err) => todo!("Need to handle lost and outdated surface errors; recreate surface"),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                let size = PhysicalSize::new(self.config.width, self.config.height);
+                self.resize(size);
+                return;
+            }
             Err(err) => {
                 warn!("Error: {:?}", err);
                 return;
@@ -136,11 +223,20 @@ impl State {
                 .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                     label: Some("Render command encoder"),
                 });
+
+        // Render straight to the swapchain unless there's a post-processing
+        // chain to feed, in which case we render into its offscreen scene target.
+        let has_post_passes = self.post_process.pass_count() > 0;
+        let scene_view = if has_post_passes {
+            self.post_process.scene_view()
+        } else {
+            &view
+        };
         {
             let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: scene_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -157,12 +253,17 @@ impl State {
 
             render_pass.set_pipeline(&self.render_pipeline);
             render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
             render_pass.set_bind_group(0, &self.model_bind_group, &[]);
-            render_pass.draw(
-                0..SQUARE_VERTICES.len() as u32,
-                0..INSTANCE_DATA.len() as u32,
-            );
+            render_pass.set_bind_group(1, self.gol.current_render_bind_group(), &[]);
+            render_pass.set_bind_group(2, &self.texture.bind_group, &[]);
+            render_pass.set_bind_group(3, &self.camera.bind_group, &[]);
+            render_pass.draw_indexed(0..self.num_indices, 0, 0..self.gol.cell_count());
+        }
+
+        if has_post_passes {
+            self.post_process
+                .render(&self.device, &self.queue, &mut command_encoder, &view);
         }
 
         self.queue.submit(std::iter::once(command_encoder.finish()));
@@ -197,11 +298,19 @@ impl State {
         let adapter_info = adapter.get_info();
         debug!("Using adapter: {:?}", adapter_info);
 
+        // WebGL only supports a cut-down subset of wgpu's limits, so every
+        // wasm32 build requests the WebGL2 downlevel limits; native backends
+        // use the full defaults.
+        #[cfg(target_arch = "wasm32")]
+        let limits = wgpu::Limits::downlevel_webgl2_defaults();
+        #[cfg(not(target_arch = "wasm32"))]
+        let limits = wgpu::Limits::default();
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Device"),
-                    limits: Default::default(),
+                    limits,
                     features: Default::default(),
                 },
                 None,
@@ -211,27 +320,25 @@ impl State {
 
         let surface_caps = surface.get_capabilities(&adapter);
 
-        surface.configure(
-            &device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
-                format: *surface_caps
-                    .formats
-                    .get(0)
-                    .expect("Surface had no supported texture formats"),
-                width: window.inner_size().width,
-                height: window.inner_size().height,
-                present_mode: *surface_caps
-                    .present_modes
-                    .get(0)
-                    .unwrap_or(&wgpu::PresentMode::Fifo),
-                alpha_mode: *surface_caps
-                    .alpha_modes
-                    .get(0)
-                    .unwrap_or(&wgpu::CompositeAlphaMode::default()),
-                view_formats: vec![],
-            },
-        );
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+            format: *surface_caps
+                .formats
+                .get(0)
+                .expect("Surface had no supported texture formats"),
+            width: window.inner_size().width,
+            height: window.inner_size().height,
+            present_mode: *surface_caps
+                .present_modes
+                .get(0)
+                .unwrap_or(&wgpu::PresentMode::Fifo),
+            alpha_mode: *surface_caps
+                .alpha_modes
+                .get(0)
+                .unwrap_or(&wgpu::CompositeAlphaMode::default()),
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
 
         let shader_module = device.create_shader_module(include_wgsl!("../shaders/shader.wgsl"));
 
@@ -245,52 +352,45 @@ impl State {
                     offset: 0,
                     shader_location: 0,
                 },
+                // tex_coords ([f32; 2])
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x2,
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                },
             ],
         };
 
+        let quad = Mesh::quad();
+
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex buffer"),
-            contents: bytemuck::cast_slice(&SQUARE_VERTICES),
+            contents: bytemuck::cast_slice(&quad.vertices),
             usage: wgpu::BufferUsages::VERTEX,
         });
 
-        let instance_data = INSTANCE_DATA.map(InstanceRaw::from);
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index buffer"),
+            contents: bytemuck::cast_slice(&quad.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let num_indices = quad.indices.len() as u32;
 
-        let instance_buffer_layout = VertexBufferLayout {
-            array_stride: std::mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Instance,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x4,
-                    offset: 0,
-                    shader_location: 1,
-                },
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x4,
-                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-                    shader_location: 2,
-                },
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x4,
-                    offset: 2 * std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-                    shader_location: 3,
-                },
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x4,
-                    offset: 3 * std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-                    shader_location: 4,
-                },
-            ],
-        };
+        let gol = GolGrid::new(&device, GRID_WIDTH, GRID_HEIGHT);
 
-        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Instance buffer"),
-            contents: bytemuck::cast_slice(&instance_data),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
+        let texture_bind_group_layout = Texture::bind_group_layout(&device);
+        let default_texture =
+            Texture::solid_color(&device, &queue, &texture_bind_group_layout, [255, 255, 255, 255]);
 
-        // let identity_matrix = cgmath::Matrix4::<f32>::identity();
-        let model_matrix = cgmath::Matrix4::from_translation(cgmath::Vector3::new(-0.5, 0.0, 0.0));
+        let camera = CameraResource::new(&device, config.width as f32 / config.height as f32);
+        // Speed is in world units per second now that `update_camera` is dt-scaled.
+        let camera_controller = CameraController::new(3.0);
+
+        let post_process = PostProcessChain::new(&device, config.width, config.height, config.format);
+
+        // The grid is positioned and sized entirely in `vs_main` from the
+        // instance index, so the model matrix stays the identity.
+        let model_matrix = cgmath::Matrix4::<f32>::identity();
         let model: [[f32; 4]; 4] = model_matrix.into();
 
         let model_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -325,7 +425,12 @@ impl State {
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render pipeline layout"),
-            bind_group_layouts: &[&model_bind_group_layout],
+            bind_group_layouts: &[
+                &model_bind_group_layout,
+                &gol.render_bind_group_layout,
+                &texture_bind_group_layout,
+                &camera.bind_group_layout,
+            ],
             push_constant_ranges: &[],
         });
 
@@ -335,10 +440,10 @@ impl State {
             vertex: wgpu::VertexState {
                 module: &shader_module,
                 entry_point: "vs_main",
-                buffers: &[vertex_buffer_layout, instance_buffer_layout],
+                buffers: &[vertex_buffer_layout],
             },
-            primitive: PrimitiveState { 
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
+            primitive: PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 ..Default::default()
             },
             depth_stencil: None,
@@ -357,8 +462,11 @@ impl State {
 
         let state = Self {
             counter: Instant::now(),
+            last_tick: Instant::now(),
+            tick_rate: TICK_RATE,
             window,
             surface,
+            config,
             _adapter: adapter,
             device,
             queue,
@@ -366,8 +474,15 @@ impl State {
             _pipeline_layout: pipeline_layout,
             render_pipeline,
             vertex_buffer,
+            index_buffer,
+            num_indices,
             model_bind_group,
-            instance_buffer,
+            gol,
+            texture_bind_group_layout,
+            texture: default_texture,
+            camera,
+            camera_controller,
+            post_process,
         };
 
         (state, event_loop)
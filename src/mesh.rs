@@ -0,0 +1,24 @@
+use crate::Vertex;
+
+/// A generic triangle-list geometry: vertices plus the indices that wind
+/// them into triangles. Replaces the old hardcoded vertex-only const arrays.
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u16>,
+}
+
+impl Mesh {
+    pub fn quad() -> Self {
+        Self {
+            vertices: crate::SQUARE_VERTICES.to_vec(),
+            indices: vec![0, 1, 2, 1, 3, 2],
+        }
+    }
+
+    pub fn triangle() -> Self {
+        Self {
+            vertices: crate::TRIANGLE_VERTICES.to_vec(),
+            indices: vec![0, 1, 2],
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::borrow::Cow;
+use std::time::Instant;
+
+use wgpu::util::DeviceExt;
+
+// Shared by every post pass: draws a full-screen triangle (no vertex buffer
+// needed) and declares the source-texture/sampler and resolution/time
+// bind groups a user-supplied fragment shader can read from.
+const FULLSCREEN_PRELUDE: &str = r#"
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32((vertex_index << 1u) & 2u);
+    let y = f32(vertex_index & 2u);
+    out.uv = vec2<f32>(x, y);
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    return out;
+}
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+struct PostUniform {
+    resolution: vec2<f32>,
+    time: f32,
+};
+@group(1) @binding(0)
+var<uniform> post: PostUniform;
+"#;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniform {
+    resolution: [f32; 2],
+    time: f32,
+    _padding: f32,
+}
+
+/// An offscreen color target a post pass (or the scene) renders into.
+struct RenderTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+impl RenderTarget {
+    fn new(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat, label: &str) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { texture, view }
+    }
+}
+
+/// One stage of the post-processing chain: a full-screen fragment shader
+/// that samples the previous stage's output and writes to its own target.
+pub struct PostPass {
+    pipeline: wgpu::RenderPipeline,
+    source_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    target: RenderTarget,
+}
+
+impl PostPass {
+    fn new(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        fragment_wgsl: &str,
+    ) -> Self {
+        let source = format!("{FULLSCREEN_PRELUDE}\n{fragment_wgsl}");
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post pass shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(source)),
+        });
+
+        let source_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Post pass source bind group layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Post pass uniform bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post pass uniform buffer"),
+            contents: bytemuck::bytes_of(&PostUniform {
+                resolution: [width as f32, height as f32],
+                time: 0.0,
+                _padding: 0.0,
+            }),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post pass uniform bind group"),
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post pass sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post pass pipeline layout"),
+            bind_group_layouts: &[&source_bind_group_layout, &uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post pass pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let target = RenderTarget::new(device, width, height, format, "Post pass target");
+
+        Self {
+            pipeline,
+            source_bind_group_layout,
+            sampler,
+            uniform_buffer,
+            uniform_bind_group,
+            target,
+        }
+    }
+
+    fn sync_uniform(&self, queue: &wgpu::Queue, width: u32, height: u32, time: f32) {
+        let uniform = PostUniform {
+            resolution: [width as f32, height as f32],
+            time,
+            _padding: 0.0,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+}
+
+/// Renders the scene to an offscreen target, then threads it through an
+/// ordered chain of full-screen post-processing passes. With zero passes
+/// added, the chain is a no-op and callers should render straight to the
+/// swapchain view instead.
+pub struct PostProcessChain {
+    scene_target: RenderTarget,
+    passes: Vec<PostPass>,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    start_time: Instant,
+}
+
+impl PostProcessChain {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+        Self {
+            scene_target: RenderTarget::new(device, width, height, format, "Scene target"),
+            passes: Vec::new(),
+            width,
+            height,
+            format,
+            start_time: Instant::now(),
+        }
+    }
+
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// The view the scene should be rendered into before this chain runs.
+    pub fn scene_view(&self) -> &wgpu::TextureView {
+        &self.scene_target.view
+    }
+
+    /// Append a new fragment-shader-only post-processing stage to the chain.
+    pub fn add_pass(&mut self, device: &wgpu::Device, fragment_wgsl: &str) {
+        self.passes
+            .push(PostPass::new(device, self.width, self.height, self.format, fragment_wgsl));
+    }
+
+    /// Re-create the scene target and every pass's target at the new size.
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.scene_target = RenderTarget::new(device, width, height, self.format, "Scene target");
+        for pass in &mut self.passes {
+            pass.target = RenderTarget::new(device, width, height, self.format, "Post pass target");
+        }
+    }
+
+    /// Run every pass in order, sampling the previous stage's output and
+    /// writing to its own target; the last pass writes to `final_view`.
+    pub fn render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        final_view: &wgpu::TextureView,
+    ) {
+        let time = self.start_time.elapsed().as_secs_f32();
+        let n = self.passes.len();
+        for i in 0..n {
+            let source_view = if i == 0 {
+                self.scene_target
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default())
+            } else {
+                self.passes[i - 1]
+                    .target
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default())
+            };
+
+            let pass = &mut self.passes[i];
+            pass.sync_uniform(queue, self.width, self.height, time);
+
+            let source_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post pass source bind group"),
+                layout: &pass.source_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&pass.sampler),
+                    },
+                ],
+            });
+
+            let target_view = if i + 1 == n { final_view } else { &pass.target.view };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post-processing pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&pass.pipeline);
+            render_pass.set_bind_group(0, &source_bind_group, &[]);
+            render_pass.set_bind_group(1, &pass.uniform_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}